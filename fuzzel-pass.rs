@@ -1,7 +1,11 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::RngCore;
+use rand::rngs::OsRng;
 use std::collections::VecDeque;
+use std::fs;
 use std::io::{Error, ErrorKind, Write};
 use std::os::unix::process::ExitStatusExt;
-use std::process::{Command, Stdio, exit};
+use std::process::{Command, Stdio};
 use std::{env, error};
 use std::{fmt, str};
 
@@ -39,21 +43,19 @@ impl From<FuzzelSelectError> for Error {
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 enum CopyFieldError {
-    SpawnFailed(Error),
-    PipeFailed(Error),
-    CopyFailed(Error),
+    SpawnFailed(&'static str, Error),
+    PipeFailed(&'static str, Error),
+    CopyFailed(&'static str, Error),
 }
 
 impl fmt::Display for CopyFieldError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            CopyFieldError::SpawnFailed(e) => write!(
-                f,
-                "Failed to spawn wl-copy! Maybe wl-clipboard is not installed?: {}",
-                e
-            ),
-            CopyFieldError::PipeFailed(e) => write!(f, "Failed to pipe the selected fields value into wl-copy!: {}", e),
-            CopyFieldError::CopyFailed(e) => write!(f, "Failed to copy to clipboard using wl-copy!: {}", e),
+            CopyFieldError::SpawnFailed(name, e) => {
+                write!(f, "Failed to spawn {}! Maybe it is not installed?: {}", name, e)
+            }
+            CopyFieldError::PipeFailed(name, e) => write!(f, "Failed to pipe the selected fields value into {}!: {}", name, e),
+            CopyFieldError::CopyFailed(name, e) => write!(f, "Failed to copy to clipboard using {}!: {}", name, e),
         }
     }
 }
@@ -68,13 +70,13 @@ impl From<CopyFieldError> for Error {
 
 #[derive(Debug)]
 enum TypeFieldError {
-    CommandFailed(Error),
+    CommandFailed(&'static str, Error),
 }
 
 impl fmt::Display for TypeFieldError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TypeFieldError::CommandFailed(e) => write!(f, "Failed to run wtype! Maybe wtype is not installed?: {}", e),
+            TypeFieldError::CommandFailed(name, e) => write!(f, "Failed to run {}! Maybe it is not installed?: {}", name, e),
         }
     }
 }
@@ -87,52 +89,246 @@ impl From<TypeFieldError> for Error {
     }
 }
 
-struct Arguments {
-    /// Type the selection instead of copying to the clipboard.
-    type_selection: bool,
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug)]
+enum PassInsertError {
+    SpawnFailed(Error),
+    PipeFailed(Error),
+    InsertFailed(Error),
+}
+
+impl fmt::Display for PassInsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassInsertError::SpawnFailed(e) => write!(f, "Failed to spawn \"pass insert\"! Maybe pass is not installed?: {}", e),
+            PassInsertError::PipeFailed(e) => write!(f, "Failed to pipe the generated password into \"pass insert\"!: {}", e),
+            PassInsertError::InsertFailed(e) => write!(f, "Failed to store the password using \"pass insert\"!: {}", e),
+        }
+    }
 }
 
-impl Arguments {
-    fn new() -> Self {
-        Self { type_selection: false }
+impl error::Error for PassInsertError {}
+
+impl From<PassInsertError> for Error {
+    fn from(value: PassInsertError) -> Self {
+        Error::new(ErrorKind::Other, value)
     }
+}
+
+/// A clipboard tool fuzzel-pass knows how to drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClipboardBackend {
+    WlCopy,
+    Xclip,
+    Xsel,
+}
 
-    fn parse() -> Self {
-        let mut arguments = Arguments::new();
+impl ClipboardBackend {
+    /// Auto-detects a usable clipboard backend from `$WAYLAND_DISPLAY`/`$DISPLAY` and `$PATH`.
+    fn detect() -> Self {
+        if env::var_os("WAYLAND_DISPLAY").is_some() && is_on_path("wl-copy") {
+            return ClipboardBackend::WlCopy;
+        }
 
-        let mut args_iter = env::args();
-        _ = args_iter.next(); // Program name
-        for arg in args_iter {
-            match arg.as_str() {
-                "-h" | "--help" => print_usage(),
-                "-t" | "--type" => arguments.type_selection = true,
-                _ => panic!("Unknown flag or value: \"{}\"!", arg.as_str()),
+        if is_on_path("xclip") {
+            ClipboardBackend::Xclip
+        } else if is_on_path("xsel") {
+            ClipboardBackend::Xsel
+        } else {
+            // Nothing found; fall back to wl-copy so the resulting spawn error names the expected tool.
+            ClipboardBackend::WlCopy
+        }
+    }
+
+    fn binary_name(&self) -> &'static str {
+        match self {
+            ClipboardBackend::WlCopy => "wl-copy",
+            ClipboardBackend::Xclip => "xclip",
+            ClipboardBackend::Xsel => "xsel",
+        }
+    }
+
+    /// Builds the command used to pipe a value onto the clipboard.
+    fn copy_command(&self) -> Command {
+        match self {
+            ClipboardBackend::WlCopy => Command::new("wl-copy"),
+            ClipboardBackend::Xclip => {
+                let mut command = Command::new("xclip");
+                command.arg("-selection").arg("clipboard");
+                command
+            }
+            ClipboardBackend::Xsel => {
+                let mut command = Command::new("xsel");
+                command.arg("--clipboard").arg("--input");
+                command
             }
         }
+    }
 
-        arguments
+    /// Builds the command used to read the current clipboard contents.
+    fn paste_command(&self) -> Command {
+        match self {
+            ClipboardBackend::WlCopy => Command::new("wl-paste"),
+            ClipboardBackend::Xclip => {
+                let mut command = Command::new("xclip");
+                command.arg("-selection").arg("clipboard").arg("-o");
+                command
+            }
+            ClipboardBackend::Xsel => {
+                let mut command = Command::new("xsel");
+                command.arg("--clipboard").arg("--output");
+                command
+            }
+        }
+    }
+
+    /// A shell one-liner that prints the current clipboard contents, for use in a detached script.
+    fn paste_shell_command(&self) -> &'static str {
+        match self {
+            ClipboardBackend::WlCopy => "wl-paste 2>/dev/null",
+            ClipboardBackend::Xclip => "xclip -selection clipboard -o 2>/dev/null",
+            ClipboardBackend::Xsel => "xsel --clipboard --output 2>/dev/null",
+        }
+    }
+
+    /// The command that reads a value from stdin and places it on the clipboard, for use in a
+    /// detached script (the value is piped in, never embedded in the script text).
+    fn copy_shell_command(&self) -> &'static str {
+        match self {
+            ClipboardBackend::WlCopy => "wl-copy",
+            ClipboardBackend::Xclip => "xclip -selection clipboard",
+            ClipboardBackend::Xsel => "xsel --clipboard --input",
+        }
+    }
+
+    /// A shell one-liner that clears the clipboard, for use in a detached script.
+    fn clear_shell_command(&self) -> &'static str {
+        match self {
+            ClipboardBackend::WlCopy => "wl-copy --clear",
+            ClipboardBackend::Xclip => "printf '' | xclip -selection clipboard",
+            ClipboardBackend::Xsel => "xsel --clipboard --clear",
+        }
     }
 }
 
-fn print_usage() {
-    eprintln!(
-        "A utility to copy passwords from pass using fuzzel.
+/// A text-typing tool fuzzel-pass knows how to drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TypeBackend {
+    Wtype,
+    Xdotool,
+}
 
-Usage: {} [options]...
+impl TypeBackend {
+    /// Auto-detects a usable typing backend from `$WAYLAND_DISPLAY` and `$PATH`.
+    fn detect() -> Self {
+        if env::var_os("WAYLAND_DISPLAY").is_some() && is_on_path("wtype") {
+            TypeBackend::Wtype
+        } else {
+            TypeBackend::Xdotool
+        }
+    }
 
-Options:
-     -t,--type
-         Type the selection instead of copying to the clipboard.
-     -h,--help
-         Show this help message.",
-        env::args().next().unwrap_or("fuzzel-pass".to_string())
-    );
+    fn binary_name(&self) -> &'static str {
+        match self {
+            TypeBackend::Wtype => "wtype",
+            TypeBackend::Xdotool => "xdotool",
+        }
+    }
+
+    /// Builds the command used to type `value` wherever the cursor is.
+    fn type_command(&self, value: &str) -> Command {
+        match self {
+            TypeBackend::Wtype => {
+                let mut command = Command::new("wtype");
+                command.arg(value);
+                command
+            }
+            TypeBackend::Xdotool => {
+                let mut command = Command::new("xdotool");
+                command.arg("type").arg("--").arg(value);
+                command
+            }
+        }
+    }
+}
+
+/// Checks whether `binary` can be found in any directory on `$PATH`.
+fn is_on_path(binary: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+/// A utility to copy passwords from pass using fuzzel.
+#[derive(Parser)]
+#[command(name = "fuzzel-pass", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Mode>,
+
+    /// Type the selection instead of copying to the clipboard.
+    #[arg(short = 't', long = "type", global = true)]
+    type_selection: bool,
+
+    /// Seconds to wait before clearing the clipboard after a copy.
+    #[arg(long, default_value_t = 45, global = true)]
+    clear: u64,
+
+    /// The clipboard tool to use. Auto-detected from $WAYLAND_DISPLAY/$DISPLAY and $PATH if not given.
+    #[arg(long, global = true)]
+    clipboard_backend: Option<ClipboardBackend>,
 
-    exit(0);
+    /// The typing tool to use. Auto-detected from $WAYLAND_DISPLAY and $PATH if not given.
+    #[arg(long, global = true)]
+    type_backend: Option<TypeBackend>,
+}
+
+/// The mode fuzzel-pass runs in; "copy" is the default when no subcommand is given.
+#[derive(Subcommand)]
+enum Mode {
+    /// Select a password using fuzzel and copy (or type) it. The default when no subcommand is given.
+    Copy,
+
+    /// Generate a new password (or diceware passphrase) and store it using "pass insert".
+    Generate {
+        /// The pass entry path to store the generated password at.
+        entry: String,
+
+        /// The length of the generated password.
+        #[arg(short, long, default_value_t = 20)]
+        length: usize,
+
+        /// Path to a newline-separated wordlist to generate a diceware passphrase from instead.
+        #[arg(long)]
+        diceware: Option<String>,
+
+        /// The amount of words to pick for a diceware passphrase.
+        #[arg(long, default_value_t = 6)]
+        words: usize,
+    },
 }
 
 fn main() -> Result<(), String> {
-    let args = Arguments::parse();
+    let cli = Cli::parse();
+    let clipboard_backend = cli.clipboard_backend.unwrap_or_else(ClipboardBackend::detect);
+    let type_backend = cli.type_backend.unwrap_or_else(TypeBackend::detect);
+
+    match &cli.command {
+        Some(Mode::Generate { entry, length, diceware, words }) => {
+            return generate_and_store(
+                entry,
+                *length,
+                diceware.as_deref(),
+                *words,
+                cli.type_selection,
+                cli.clear,
+                clipboard_backend,
+                type_backend,
+            );
+        }
+        // "copy" is the default mode, and falls through to the rest of this function.
+        None | Some(Mode::Copy) => {}
+    }
 
     // Get all passwords from "pass list"
     let pass_list = Command::new("pass")
@@ -222,67 +418,197 @@ fn main() -> Result<(), String> {
     }
 
     // Copy selection to clipboard or type when that flag is passed
-    if args.type_selection {
-        type_field_value(selected_field.unwrap().1)
-            .map_err(|e| format!("Error while typing the selected fields value using wl-copy: {}", e))?;
+    if cli.type_selection {
+        type_field_value(selected_field.unwrap().1, type_backend)
+            .map_err(|e| format!("Error while typing the selected fields value: {}", e))?;
     } else {
-        copy_field_value(selected_field.unwrap().1).map_err(|e| {
-            format!(
-                "Error while copying the selected fields value to the clipboard using wl-copy: {}",
-                e
-            )
-        })?;
+        copy_field_value(selected_field.unwrap().1, cli.clear, clipboard_backend)
+            .map_err(|e| format!("Error while copying the selected fields value to the clipboard: {}", e))?;
     }
 
     Ok(())
 }
 
-/// Types the passed value wherever the cursor is using wtype.
-fn type_field_value(value: &str) -> Result<(), TypeFieldError> {
-    let wtype_status = Command::new("wtype")
-        .arg(value)
+/// Types the passed value wherever the cursor is using `backend`.
+fn type_field_value(value: &str, backend: TypeBackend) -> Result<(), TypeFieldError> {
+    let status = backend
+        .type_command(value)
         .status()
-        .map_err(TypeFieldError::CommandFailed)?;
+        .map_err(|e| TypeFieldError::CommandFailed(backend.binary_name(), e))?;
 
-    if !wtype_status.success() {
-        return Err(TypeFieldError::CommandFailed(Error::new(
-            ErrorKind::Other,
-            format!(
-                "wtype failed with exit code: {}",
-                wtype_status.code().unwrap_or(
-                    wtype_status
-                        .stopped_signal()
-                        .expect("If this fails I shoot myself in the foot!")
-                )
+    if !status.success() {
+        return Err(TypeFieldError::CommandFailed(
+            backend.binary_name(),
+            Error::new(
+                ErrorKind::Other,
+                format!(
+                    "{} failed with exit code: {}",
+                    backend.binary_name(),
+                    status
+                        .code()
+                        .unwrap_or(status.stopped_signal().expect("If this fails I shoot myself in the foot!"))
+                ),
             ),
-        )));
+        ));
     }
 
     Ok(())
 }
 
-/// Copies the passed value to the clipboard using wl-copy.
-fn copy_field_value(value: &str) -> Result<(), CopyFieldError> {
-    let mut wl_copy = Command::new("wl-copy")
+/// Copies the passed value to the clipboard using `backend`, then schedules a detached clear after
+/// `clear_timeout_secs` seconds so the secret doesn't linger on the clipboard indefinitely.
+fn copy_field_value(value: &str, clear_timeout_secs: u64, backend: ClipboardBackend) -> Result<(), CopyFieldError> {
+    let previous_clipboard = read_clipboard(backend);
+
+    let mut copy_process = backend
+        .copy_command()
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .spawn()
-        .map_err(CopyFieldError::SpawnFailed)?;
+        .map_err(|e| CopyFieldError::SpawnFailed(backend.binary_name(), e))?;
 
-    // Pipe the selected fields value into wl-copy
-    if let Some(stdin) = &mut wl_copy.stdin {
-        stdin.write_all(value.as_bytes()).map_err(CopyFieldError::PipeFailed)?;
+    // Pipe the selected fields value into the clipboard backend
+    if let Some(stdin) = &mut copy_process.stdin {
+        stdin
+            .write_all(value.as_bytes())
+            .map_err(|e| CopyFieldError::PipeFailed(backend.binary_name(), e))?;
     }
 
-    // Check wl-copy status
-    let wl_copy_status = wl_copy.wait().map_err(CopyFieldError::CopyFailed)?;
-    if !wl_copy_status.success() {
-        return Err(CopyFieldError::CopyFailed(Error::new(
+    // Check the clipboard backend's status
+    let copy_status = copy_process
+        .wait()
+        .map_err(|e| CopyFieldError::CopyFailed(backend.binary_name(), e))?;
+    if !copy_status.success() {
+        return Err(CopyFieldError::CopyFailed(
+            backend.binary_name(),
+            Error::new(
+                ErrorKind::Other,
+                format!(
+                    "{} failed with exit code: {}",
+                    backend.binary_name(),
+                    copy_status
+                        .code()
+                        .unwrap_or(copy_status.stopped_signal().expect("If this fails I shoot myself in the foot!"))
+                ),
+            ),
+        ));
+    }
+
+    schedule_clipboard_clear(value, previous_clipboard, clear_timeout_secs, backend);
+
+    Ok(())
+}
+
+/// Reads the current clipboard contents using `backend`, returning `None` if the clipboard is
+/// empty or the backend fails (which it does when there is nothing to paste).
+fn read_clipboard(backend: ClipboardBackend) -> Option<String> {
+    let paste = backend.paste_command().output().ok()?;
+    if !paste.status.success() {
+        return None;
+    }
+
+    String::from_utf8(paste.stdout).ok()
+}
+
+/// Forks a detached child that waits `timeout_secs` seconds, then restores `previous_clipboard`
+/// (or clears the clipboard if it was empty) — but only if the clipboard still holds `secret`, so
+/// a value the user copied in the meantime isn't clobbered.
+///
+/// `secret` and `previous_clipboard` are piped to the child over stdin rather than embedded in its
+/// argv, so they don't sit readable in the process table (`ps auxww`, `/proc/<pid>/cmdline`) for
+/// the whole `timeout_secs` window. `previous_clipboard` is written last and un-delimited, with the
+/// script reading it via `cat` rather than `read -r`, so embedded newlines in a multi-line previous
+/// clipboard value (e.g. a copied paragraph or snippet) round-trip intact instead of being
+/// truncated to their first line.
+fn schedule_clipboard_clear(secret: &str, previous_clipboard: Option<String>, timeout_secs: u64, backend: ClipboardBackend) {
+    let script = format!(
+        "IFS= read -r secret; IFS= read -r has_previous; previous=$(cat); \
+         sleep {timeout_secs}; \
+         if [ \"$({paste_command})\" = \"$secret\" ]; then \
+             if [ \"$has_previous\" = 1 ]; then printf %s \"$previous\" | {copy_command}; else {clear_command}; fi; \
+         fi",
+        timeout_secs = timeout_secs,
+        paste_command = backend.paste_shell_command(),
+        copy_command = backend.copy_shell_command(),
+        clear_command = backend.clear_shell_command(),
+    );
+
+    // Intentionally not waited on: this detaches once we exit, mirroring how "pass -c" backgrounds its own clear.
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return,
+    };
+
+    if let Some(stdin) = &mut child.stdin {
+        let _ = writeln!(stdin, "{}", secret);
+        let _ = writeln!(stdin, "{}", if previous_clipboard.is_some() { 1 } else { 0 });
+        if let Some(previous) = previous_clipboard {
+            let _ = stdin.write_all(previous.as_bytes());
+        }
+    }
+}
+
+/// Generates a new password (or diceware passphrase), stores it at `entry` using "pass insert",
+/// then copies or types it depending on `type_selection`.
+#[allow(clippy::too_many_arguments)]
+fn generate_and_store(
+    entry: &str,
+    length: usize,
+    diceware_wordlist: Option<&str>,
+    diceware_words: usize,
+    type_selection: bool,
+    clear_timeout: u64,
+    clipboard_backend: ClipboardBackend,
+    type_backend: TypeBackend,
+) -> Result<(), String> {
+    let value = if let Some(wordlist) = diceware_wordlist {
+        generate_diceware_passphrase(wordlist, diceware_words)?
+    } else {
+        generate_password(length)?
+    };
+
+    insert_password(entry, &value).map_err(|e| format!("Error while storing the generated password: {}", e))?;
+
+    if type_selection {
+        type_field_value(&value, type_backend).map_err(|e| format!("Error while typing the generated password: {}", e))?;
+    } else {
+        copy_field_value(&value, clear_timeout, clipboard_backend)
+            .map_err(|e| format!("Error while copying the generated password to the clipboard: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Stores `value` at `entry` using "pass insert -m".
+fn insert_password(entry: &str, value: &str) -> Result<(), PassInsertError> {
+    let mut pass_insert = Command::new("pass")
+        .arg("insert")
+        .arg("-m")
+        .arg(entry)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(PassInsertError::SpawnFailed)?;
+
+    // Pipe the generated password into "pass insert -m" (reads until EOF, no confirmation needed)
+    if let Some(stdin) = &mut pass_insert.stdin {
+        stdin.write_all(value.as_bytes()).map_err(PassInsertError::PipeFailed)?;
+    }
+
+    let pass_insert_status = pass_insert.wait().map_err(PassInsertError::InsertFailed)?;
+    if !pass_insert_status.success() {
+        return Err(PassInsertError::InsertFailed(Error::new(
             ErrorKind::Other,
             format!(
-                "wl-copy failed with exit code: {}",
-                wl_copy_status.code().unwrap_or(
-                    wl_copy_status
+                "pass insert failed with exit code: {}",
+                pass_insert_status.code().unwrap_or(
+                    pass_insert_status
                         .stopped_signal()
                         .expect("If this fails I shoot myself in the foot!")
                 )
@@ -293,6 +619,84 @@ fn copy_field_value(value: &str) -> Result<(), CopyFieldError> {
     Ok(())
 }
 
+/// Generates a random password of `length` containing uppercase, lowercase, numeric and special
+/// characters, regenerating until every character category is represented at least once.
+fn generate_password(length: usize) -> Result<String, String> {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%^&*()-_=+[]{}";
+    const MIN_LENGTH: usize = 4; // One character per class (upper, lower, digit, special)
+
+    if length < MIN_LENGTH {
+        return Err(format!(
+            "Expected a length of at least {} to fit every character class, but got: {}",
+            MIN_LENGTH, length
+        ));
+    }
+
+    loop {
+        let candidate: String = (0..length).map(|_| CHARSET[random_index(CHARSET.len())] as char).collect();
+
+        if has_every_character_class(&candidate) {
+            return Ok(candidate);
+        }
+    }
+}
+
+/// Checks whether `value` contains at least one uppercase, lowercase, numeric and special character.
+fn has_every_character_class(value: &str) -> bool {
+    let mut has_upper = false;
+    let mut has_lower = false;
+    let mut has_digit = false;
+    let mut has_special = false;
+
+    for c in value.chars() {
+        if c.is_ascii_uppercase() {
+            has_upper = true;
+        } else if c.is_ascii_lowercase() {
+            has_lower = true;
+        } else if c.is_ascii_digit() {
+            has_digit = true;
+        } else {
+            has_special = true;
+        }
+    }
+
+    has_upper && has_lower && has_digit && has_special
+}
+
+/// Generates a diceware-style passphrase by picking `word_count` random words from `wordlist_path`.
+fn generate_diceware_passphrase(wordlist_path: &str, word_count: usize) -> Result<String, String> {
+    if word_count == 0 {
+        return Err("Expected at least 1 word, but got: 0".to_string());
+    }
+
+    let contents = fs::read_to_string(wordlist_path)
+        .map_err(|e| format!("Failed to read the diceware wordlist \"{}\": {}", wordlist_path, e))?;
+
+    let words = contents.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<&str>>();
+    if words.is_empty() {
+        return Err(format!("The diceware wordlist \"{}\" is empty!", wordlist_path));
+    }
+
+    Ok((0..word_count)
+        .map(|_| words[random_index(words.len())])
+        .collect::<Vec<&str>>()
+        .join("-"))
+}
+
+/// Draws a uniformly distributed index in `0..exclusive_max` using `OsRng`, rejection-sampling to
+/// avoid modulo bias.
+fn random_index(exclusive_max: usize) -> usize {
+    let mut rng = OsRng;
+    let limit = u32::MAX - (u32::MAX % exclusive_max as u32);
+
+    loop {
+        let value = rng.next_u32();
+        if value < limit {
+            return (value % exclusive_max as u32) as usize;
+        }
+    }
+}
+
 /// Select and return a value from the given list of values using fuzzel.
 fn fuzzel_select_value(values: &[String]) -> Result<String, FuzzelSelectError> {
     // Spawn fuzzel to select a value
@@ -379,16 +783,113 @@ fn get_line_indent(line: &str) -> usize {
     prefix.chars().filter(|&c| c == ' ' || c == '│').count() / 4
 }
 
-/// Check if a password list line is a directory.
+/// Legacy (non-extended) SGR parameter codes that mark an entry as a directory (blue foreground,
+/// as used by `tree`'s default `di=01;34` and most `LS_COLORS` themes). Extend this list to
+/// recognize other legacy palettes; 256-color and truecolor foregrounds are handled separately by
+/// `is_directory_sgr`.
+const DIRECTORY_SGR_CODES: [&str; 2] = ["34", "94"];
+
+/// Check if a password list line is a directory by inspecting its SGR escape sequences, rather
+/// than assuming one specific color sequence. Recognizes the legacy 8/16-color blue codes as well
+/// as blue-ish 256-color (`38;5;N`) and truecolor (`38;2;r;g;b`) extended foregrounds, since themes
+/// like `trapd00r/LS_COLORS` color directories with those instead.
 fn is_line_directory(line: &str) -> bool {
-    line.contains("\u{1b}[01;34m") && line.contains("\u{1b}[0m")
+    scan_sgr_codes(line).1.iter().any(|params| is_directory_sgr(params))
+}
+
+/// Checks whether an SGR parameter string (the part between `\x1b[` and `m`) marks a directory.
+fn is_directory_sgr(params: &str) -> bool {
+    let codes: Vec<&str> = params.split(';').collect();
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            code if DIRECTORY_SGR_CODES.contains(&code) => return true,
+            "38" => match codes.get(i + 1) {
+                Some(&"5") => {
+                    if let Some(color) = codes.get(i + 2).and_then(|c| c.parse::<u8>().ok()) {
+                        if is_blue_256_color(color) {
+                            return true;
+                        }
+                    }
+                    i += 3;
+                    continue;
+                }
+                Some(&"2") => {
+                    let channels = (
+                        codes.get(i + 2).and_then(|c| c.parse::<u8>().ok()),
+                        codes.get(i + 3).and_then(|c| c.parse::<u8>().ok()),
+                        codes.get(i + 4).and_then(|c| c.parse::<u8>().ok()),
+                    );
+                    if let (Some(r), Some(g), Some(b)) = channels {
+                        if is_blue_truecolor(r, g, b) {
+                            return true;
+                        }
+                    }
+                    i += 5;
+                    continue;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+
+    false
+}
+
+/// Checks whether an extended 256-color palette index (the `N` in `38;5;N`) is blue-ish: the
+/// standard palette's blue/bright-blue entries (4, 12), or a 216-color cube entry where blue is
+/// the clearly dominant channel.
+fn is_blue_256_color(color: u8) -> bool {
+    match color {
+        4 | 12 => true,
+        16..=231 => {
+            let cube = color - 16;
+            let (r, g, b) = (cube / 36, (cube / 6) % 6, cube % 6);
+            b > r && b > g && b >= 3
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether a truecolor RGB triple (the `r;g;b` in `38;2;r;g;b`) is blue-ish: blue is
+/// clearly the dominant channel.
+fn is_blue_truecolor(r: u8, g: u8, b: u8) -> bool {
+    b > r && b > g
 }
 
-/// Strip out the ANSI codes and any non-breaking spaces from a password list line.
+/// Strip out the ANSI SGR escape codes and any non-breaking spaces from a password list line.
 fn strip_ansi_line(line: &str) -> String {
-    line.replace("\u{1b}[01;34m", "")
-        .replace("\u{1b}[0m", "")
-        .replace("\u{a0}", " ")
+    scan_sgr_codes(line).0.replace('\u{a0}', " ")
+}
+
+/// Scans `line` for ANSI SGR escape sequences (`\x1b[<params>m`), returning the line with every
+/// sequence removed, along with the parameter string of each sequence found, in order.
+fn scan_sgr_codes(line: &str) -> (String, Vec<String>) {
+    let mut stripped = String::with_capacity(line.len());
+    let mut codes = Vec::new();
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' || chars.peek() != Some(&'[') {
+            stripped.push(c);
+            continue;
+        }
+
+        chars.next(); // consume '['
+        let mut params = String::new();
+        for next in chars.by_ref() {
+            if next == 'm' {
+                break;
+            }
+            params.push(next);
+        }
+        codes.push(params);
+    }
+
+    (stripped, codes)
 }
 
 /// Remove unwanted characters in a password list line.
@@ -400,3 +901,42 @@ fn strip_line(line: &str) -> String {
         .trim_start_matches(|c: char| c.is_whitespace() || "└├─│".contains(c))
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_classic_blue_directory() {
+        assert!(is_line_directory("\u{1b}[01;34mdir\u{1b}[0m"));
+        assert!(is_line_directory("\u{1b}[94mdir\u{1b}[0m"));
+    }
+
+    #[test]
+    fn detects_256_color_blue_directory() {
+        // trapd00r/LS_COLORS-style cube blue, e.g. `di=38;5;33`.
+        assert!(is_line_directory("\u{1b}[38;5;33mdir\u{1b}[0m"));
+        // Standard-palette bright blue addressed via the extended form.
+        assert!(is_line_directory("\u{1b}[38;5;12mdir\u{1b}[0m"));
+    }
+
+    #[test]
+    fn detects_truecolor_blue_directory() {
+        assert!(is_line_directory("\u{1b}[38;2;0;0;238mdir\u{1b}[0m"));
+    }
+
+    #[test]
+    fn does_not_flag_non_blue_colors_as_directories() {
+        assert!(!is_line_directory("\u{1b}[32mfile\u{1b}[0m"));
+        assert!(!is_line_directory("\u{1b}[38;5;196mfile\u{1b}[0m"));
+        assert!(!is_line_directory("\u{1b}[38;2;238;0;0mfile\u{1b}[0m"));
+        assert!(!is_line_directory("plain text"));
+    }
+
+    #[test]
+    fn scan_sgr_codes_strips_escapes_and_collects_params() {
+        let (stripped, codes) = scan_sgr_codes("\u{1b}[01;34mdir\u{1b}[0m");
+        assert_eq!(stripped, "dir");
+        assert_eq!(codes, vec!["01;34", "0"]);
+    }
+}